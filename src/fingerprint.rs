@@ -0,0 +1,50 @@
+//! Deterministic, keyed fingerprinting of card numbers.
+//!
+//! The exact-payload hash in [`crate::storage::HashInterface`] mixes every request field and is
+//! unsalted, so it only detects byte-identical repeats. A fingerprint instead covers only the
+//! normalized PAN, keyed with a dedicated, rotatable secret, so the vault can tell that the same
+//! card was stored under a different customer, expiry, or name.
+
+use error_stack::ResultExt;
+use masking::Secret;
+
+use crate::{
+    crypto::{hmac::HmacSha512, Encode},
+    error,
+};
+
+/// How a store request should react when a fingerprint match is found against an existing,
+/// already-vaulted card
+#[derive(Debug, Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateCheckMode {
+    /// Dedup only on the exact-payload hash, ignoring fingerprint matches (default)
+    #[default]
+    Off,
+    /// Fail the request if the PAN is already vaulted for this merchant under any customer,
+    /// including a repeat of an identical request
+    Reject,
+    /// Reuse the already-vaulted content instead of storing this request's payload: this
+    /// customer's existing row is returned, or a new row for this customer referencing that
+    /// content is inserted if there is none
+    ReturnExisting,
+}
+
+/// Strip everything but digits from a card number so formatting differences (spaces, hyphens)
+/// don't change the fingerprint of the same PAN
+fn normalize_card_number(card_number: &str) -> String {
+    card_number.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+/// Compute a keyed fingerprint over the normalized card number with HMAC-SHA512, so the same PAN
+/// always fingerprints identically but the fingerprint can't be reproduced without `key`
+pub fn compute(
+    card_number: &str,
+    key: &Secret<Vec<u8>>,
+) -> error_stack::Result<Vec<u8>, error::ApiError> {
+    let normalized = normalize_card_number(card_number);
+
+    HmacSha512::new(key.clone())
+        .encode(normalized.into_bytes())
+        .change_context(error::ApiError::EncodingError)
+}
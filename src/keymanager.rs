@@ -0,0 +1,70 @@
+//! Master-key management.
+//!
+//! The vault can hold more than one master key at a time, each identified by a `key_version`.
+//! A merchant's `enc_key` (DEK) is wrapped under whichever version was current when it was
+//! written, recorded alongside it; new merchants always use [`MasterKeyStore::current`]. This
+//! lets a compromised master key be rotated online via `MerchantInterface::rotate_master_key`
+//! without ever touching card ciphertext - only each DEK is re-wrapped.
+
+use std::collections::HashMap;
+
+use crate::{crypto::Encryption, error};
+
+/// A set of master keys identified by version, with one designated current for new writes
+#[derive(Clone)]
+pub struct MasterKeyStore<Algorithm> {
+    keys: HashMap<i32, Algorithm>,
+    current_version: i32,
+}
+
+impl<Algorithm> MasterKeyStore<Algorithm>
+where
+    Algorithm: Encryption<Vec<u8>, Vec<u8>>,
+{
+    /// Build a key store from `(version, algorithm)` pairs, with `current_version` selected for
+    /// encrypting new or rotated DEKs
+    pub fn new(keys: HashMap<i32, Algorithm>, current_version: i32) -> Self {
+        Self {
+            keys,
+            current_version,
+        }
+    }
+
+    /// The version and algorithm new merchants' DEKs should be wrapped under
+    pub fn current(&self) -> error_stack::Result<(i32, &Algorithm), error::StorageError> {
+        self.get(self.current_version)
+            .map(|algorithm| (self.current_version, algorithm))
+    }
+
+    /// The algorithm identified by `version`, used to unwrap a DEK encrypted under an older key
+    pub fn get(&self, version: i32) -> error_stack::Result<&Algorithm, error::StorageError> {
+        self.keys
+            .get(&version)
+            .ok_or_else(|| error_stack::report!(error::StorageError::MasterKeyNotFound(version)))
+    }
+
+    pub fn current_version(&self) -> i32 {
+        self.current_version
+    }
+}
+
+impl MasterKeyStore<crate::crypto::aes::GcmAes256> {
+    /// Build the active set of master keys from configuration
+    pub fn from_config(secrets: &crate::config::Secrets) -> Self {
+        let keys = secrets
+            .master_keys
+            .iter()
+            .map(|(version, key)| (*version, crate::crypto::aes::GcmAes256::new(key.clone())))
+            .collect();
+
+        Self::new(keys, secrets.current_key_version)
+    }
+}
+
+/// Outcome of a `rotate_master_key` run for a tenant
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RotationSummary {
+    pub tenant_id: String,
+    pub rotated_count: usize,
+    pub to_version: i32,
+}
@@ -0,0 +1,70 @@
+use axum::{extract, routing::post, Json};
+
+#[cfg(feature = "middleware")]
+use axum::middleware;
+
+use error_stack::ResultExt;
+
+use crate::{
+    app::AppState,
+    error::{self, LogReport},
+    keymanager,
+    storage::{LedgerInterface, MerchantInterface},
+};
+
+#[cfg(feature = "middleware")]
+use crate::middleware as custom_middleware;
+
+pub mod types;
+
+///
+/// Function for creating the server that is specifically handling admin/operational APIs
+///
+#[allow(clippy::let_and_return)]
+pub fn serve(#[cfg(feature = "middleware")] state: AppState) -> axum::Router<AppState> {
+    let router = axum::Router::new()
+        .route("/ledger/verify", post(verify_ledger))
+        .route("/master-key/rotate", post(rotate_master_key));
+
+    #[cfg(feature = "middleware")]
+    {
+        router.layer(middleware::from_fn_with_state(
+            state,
+            custom_middleware::middleware,
+        ))
+    }
+    #[cfg(not(feature = "middleware"))]
+    router
+}
+
+/// `/admin/ledger/verify` replays the operation ledger from its latest checkpoint and reports
+/// whether the hash chain is still intact
+pub async fn verify_ledger(
+    extract::State(state): extract::State<AppState>,
+) -> Result<Json<types::VerifyLedgerResponse>, error::ApiError> {
+    let result = state
+        .db
+        .verify_ledger(&state.config.secrets.ledger_checkpoint_key)
+        .await
+        .change_context(error::ApiError::DatabaseRetrieveFailed("ledger"))
+        .report_unwrap()?;
+
+    Ok(Json(result.into()))
+}
+
+/// `/admin/master-key/rotate` re-encrypts every merchant's dek for the configured tenant under
+/// the current master key, so a compromised master key can be remediated online
+pub async fn rotate_master_key(
+    extract::State(state): extract::State<AppState>,
+) -> Result<Json<types::RotateMasterKeyResponse>, error::ApiError> {
+    let master_keys = keymanager::MasterKeyStore::from_config(&state.config.secrets);
+
+    let summary = state
+        .db
+        .rotate_master_key(&state.config.secrets.tenant, &master_keys)
+        .await
+        .change_context(error::ApiError::DatabaseInsertFailed("merchant"))
+        .report_unwrap()?;
+
+    Ok(Json(summary.into()))
+}
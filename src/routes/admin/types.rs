@@ -0,0 +1,37 @@
+use crate::{keymanager, storage::ledger};
+
+/// Response for `/admin/ledger/verify`
+#[derive(Debug, serde::Serialize)]
+pub struct VerifyLedgerResponse {
+    pub verified: bool,
+    pub head: String,
+    pub height: i64,
+}
+
+impl From<ledger::VerificationResult> for VerifyLedgerResponse {
+    fn from(result: ledger::VerificationResult) -> Self {
+        Self {
+            verified: result.verified,
+            head: hex::encode(result.head_hash),
+            height: result.height,
+        }
+    }
+}
+
+/// Response for `/admin/master-key/rotate`
+#[derive(Debug, serde::Serialize)]
+pub struct RotateMasterKeyResponse {
+    pub tenant_id: String,
+    pub rotated_count: usize,
+    pub to_version: i32,
+}
+
+impl From<keymanager::RotationSummary> for RotateMasterKeyResponse {
+    fn from(summary: keymanager::RotationSummary) -> Self {
+        Self {
+            tenant_id: summary.tenant_id,
+            rotated_count: summary.rotated_count,
+            to_version: summary.to_version,
+        }
+    }
+}
@@ -3,21 +3,15 @@ use axum::{extract, routing::post, Json};
 #[cfg(feature = "middleware")]
 use axum::middleware;
 
-use error_stack::ResultExt;
-use masking::ExposeInterface;
-
-use crate::{
-    app::AppState,
-    crypto::{aes::GcmAes256, sha::Sha512, Encode},
-    error::{self, LogReport},
-    storage::{HashInterface, LockerInterface, MerchantInterface},
-};
+use crate::{app::AppState, error, storage::CARD_DATA_TYPE};
 
 #[cfg(feature = "middleware")]
 use crate::middleware as custom_middleware;
 
 use self::types::Validation;
 
+use super::vault;
+
 mod transformers;
 pub mod types;
 
@@ -42,171 +36,37 @@ pub fn serve(#[cfg(feature = "middleware")] state: AppState) -> axum::Router<App
     router
 }
 
-/// `/data/add` handling the requirement of storing cards
+/// `/data/add` handling the requirement of storing cards, a thin specialization of
+/// `/data/vault/add` with the data type fixed to [`CARD_DATA_TYPE`]
 pub async fn add_card(
     extract::State(state): extract::State<AppState>,
     Json(request): Json<types::StoreCardRequest>,
 ) -> Result<Json<types::StoreCardResponse>, error::ApiError> {
     request.validate()?;
 
-    let master_encryption = GcmAes256::new(state.config.secrets.master_key);
-    let merchant = state
-        .db
-        .find_or_create_by_merchant_id(
-            &request.merchant_id,
-            &state.config.secrets.tenant,
-            &master_encryption,
-        )
-        .await
-        .change_context(error::ApiError::RetrieveDataFailed("merchant"))
-        .report_unwrap()?;
-
-    let merchant_dek = GcmAes256::new(merchant.enc_key.expose());
-
-    let hash_data = serde_json::to_vec(&request.data)
-        .change_context(error::ApiError::EncodingError)
-        .and_then(|data| {
-            (Sha512)
-                .encode(data)
-                .change_context(error::ApiError::EncodingError)
-        })
-        .report_unwrap()?;
-
-    let optional_hash_table = state
-        .db
-        .find_by_data_hash(&hash_data)
-        .await
-        .change_context(error::ApiError::DatabaseRetrieveFailed("hash_table"))
-        .report_unwrap()?;
-
-    let output = match optional_hash_table {
-        Some(hash_table) => {
-            let stored_data = state
-                .db
-                .find_by_hash_id_merchant_id_customer_id(
-                    &hash_table.hash_id,
-                    &state.config.secrets.tenant,
-                    &request.merchant_id,
-                    &request.merchant_customer_id,
-                    &merchant_dek,
-                )
-                .await
-                .change_context(error::ApiError::DatabaseRetrieveFailed("locker"))
-                .report_unwrap()?;
-
-            match stored_data {
-                Some(data) => data,
-                None => state
-                    .db
-                    .insert_or_get_from_locker(
-                        (
-                            request,
-                            state.config.secrets.tenant.as_str(),
-                            hash_table.hash_id.as_str(),
-                        )
-                            .try_into()?,
-                        &merchant_dek,
-                    )
-                    .await
-                    .change_context(error::ApiError::DatabaseInsertFailed("locker"))
-                    .report_unwrap()?,
-            }
-        }
-        None => {
-            let hash_table = state
-                .db
-                .insert_hash(hash_data)
-                .await
-                .change_context(error::ApiError::DatabaseInsertFailed("hash_table"))
-                .report_unwrap()?;
+    let (_, response) = vault::store(&state, CARD_DATA_TYPE, request.into()).await?;
 
-            state
-                .db
-                .insert_or_get_from_locker(
-                    (
-                        request,
-                        state.config.secrets.tenant.as_str(),
-                        hash_table.hash_id.as_str(),
-                    )
-                        .try_into()?,
-                    &merchant_dek,
-                )
-                .await
-                .change_context(error::ApiError::DatabaseInsertFailed("locker"))
-                .report_unwrap()?
-        }
-    };
-
-    Ok(Json(output.into()))
+    Ok(Json(response.into()))
 }
 
-/// `/data/delete` handling the requirement of deleting cards
+/// `/data/delete` handling the requirement of deleting cards, a thin specialization of
+/// `/data/vault/delete` with the data type fixed to [`CARD_DATA_TYPE`]
 pub async fn delete_card(
     extract::State(state): extract::State<AppState>,
     Json(request): Json<types::DeleteCardRequest>,
 ) -> Result<Json<types::DeleteCardResponse>, error::ApiError> {
-    let master_key = GcmAes256::new(state.config.secrets.master_key.clone());
-
-    let _merchant = state
-        .db
-        .find_by_merchant_id(
-            &request.merchant_id,
-            &state.config.secrets.tenant,
-            &master_key,
-        )
-        .await
-        .change_context(error::ApiError::DatabaseRetrieveFailed("merchant"))
-        .report_unwrap()?;
-
-    let _delete_status = state
-        .db
-        .delete_from_locker(
-            request.card_reference.into(),
-            &state.config.secrets.tenant,
-            &request.merchant_id,
-            &request.merchant_customer_id,
-        )
-        .await
-        .change_context(error::ApiError::DatabaseDeleteFailed("locker"))
-        .report_unwrap()?;
+    let response = vault::delete(&state, CARD_DATA_TYPE, request.into()).await?;
 
-    Ok(Json(types::DeleteCardResponse {
-        status: types::Status::Ok,
-    }))
+    Ok(Json(response.into()))
 }
 
-/// `/data/retrieve` handling the requirement of retrieving cards
+/// `/data/retrieve` handling the requirement of retrieving cards, a thin specialization of
+/// `/data/vault/retrieve` with the data type fixed to [`CARD_DATA_TYPE`]
 pub async fn retrieve_card(
     extract::State(state): extract::State<AppState>,
     Json(request): Json<types::RetrieveCardRequest>,
 ) -> Result<Json<types::RetrieveCardResponse>, error::ApiError> {
-    let master_key = GcmAes256::new(state.config.secrets.master_key.clone());
-
-    let merchant = state
-        .db
-        .find_by_merchant_id(
-            &request.merchant_id,
-            &state.config.secrets.tenant,
-            &master_key,
-        )
-        .await
-        .change_context(error::ApiError::DatabaseDeleteFailed("locker"))
-        .report_unwrap()?;
-
-    let merchant_dek = GcmAes256::new(merchant.enc_key.expose());
-
-    let card = state
-        .db
-        .find_by_locker_id_merchant_id_customer_id(
-            request.card_reference.into(),
-            &state.config.secrets.tenant,
-            &request.merchant_id,
-            &request.merchant_customer_id,
-            &merchant_dek,
-        )
-        .await
-        .change_context(error::ApiError::DatabaseDeleteFailed("locker"))
-        .report_unwrap()?;
+    let response = vault::retrieve(&state, CARD_DATA_TYPE, request.into()).await?;
 
-    Ok(Json(card.try_into()?))
+    Ok(Json(response.try_into()?))
 }
@@ -0,0 +1,564 @@
+use axum::{extract, routing::post, Json};
+
+#[cfg(feature = "middleware")]
+use axum::middleware;
+
+use error_stack::ResultExt;
+use masking::{ExposeInterface, PeekInterface};
+
+use crate::{
+    app::AppState,
+    crypto::{aes::GcmAes256, sha::Sha512, Encode},
+    error::{self, LogReport},
+    fingerprint, keymanager,
+    storage::{
+        event, ledger, DataVaultInterface, EventInterface, HashInterface, LedgerInterface,
+        MerchantInterface,
+    },
+};
+
+#[cfg(feature = "middleware")]
+use crate::middleware as custom_middleware;
+
+mod transformers;
+pub mod types;
+
+///
+/// Function for creating the server that is specifically handling the generic, content-addressed
+/// data vault api
+///
+#[allow(clippy::let_and_return)]
+pub fn serve(#[cfg(feature = "middleware")] state: AppState) -> axum::Router<AppState> {
+    let router = axum::Router::new()
+        .route("/add", post(add_data))
+        .route("/delete", post(delete_data))
+        .route("/retrieve", post(retrieve_data));
+
+    #[cfg(feature = "middleware")]
+    {
+        router.layer(middleware::from_fn_with_state(
+            state,
+            custom_middleware::middleware,
+        ))
+    }
+    #[cfg(not(feature = "middleware"))]
+    router
+}
+
+/// `/data/vault/add` storing an arbitrary opaque secret under the caller-supplied `data_type`
+pub async fn add_data(
+    extract::State(state): extract::State<AppState>,
+    Json(request): Json<types::StoreDataRequest>,
+) -> Result<Json<types::StoreDataResponse>, error::ApiError> {
+    let data_type = request.data_type.clone();
+    let (_, response) = store(&state, &data_type, request).await?;
+
+    Ok(Json(response))
+}
+
+/// Look up vaulted data by its content hash, inserting it on a miss, and emit the audit event
+/// and ledger records for the attempt. Shared by the generic `/data/vault` routes and the
+/// card-specific `/data` routes, which call this with [`crate::storage::CARD_DATA_TYPE`].
+pub(crate) async fn store(
+    state: &AppState,
+    data_type: &str,
+    request: types::StoreDataRequest,
+) -> Result<(String, types::StoreDataResponse), error::ApiError> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let merchant_id = request.merchant_id.clone();
+    let customer_id = request.merchant_customer_id.clone();
+    let started_at = std::time::Instant::now();
+
+    let result = store_inner(state, data_type, request).await;
+
+    state
+        .db
+        .log_event(event::VaultEvent {
+            request_id,
+            event_type: event::EventType::AddData,
+            tenant_id: state.config.secrets.tenant.clone(),
+            merchant_id,
+            customer_id,
+            data_type: data_type.to_owned(),
+            reference: result.as_ref().ok().map(|output| output.0.clone()),
+            outcome: if result.is_ok() {
+                event::EventOutcome::Success
+            } else {
+                event::EventOutcome::Failure
+            },
+            latency_ms: started_at.elapsed().as_millis(),
+        })
+        .await;
+
+    result
+}
+
+async fn store_inner(
+    state: &AppState,
+    data_type: &str,
+    request: types::StoreDataRequest,
+) -> Result<(String, types::StoreDataResponse), error::ApiError> {
+    let master_keys = keymanager::MasterKeyStore::from_config(&state.config.secrets);
+    let (merchant, merchant_created) = state
+        .db
+        .find_or_create_by_merchant_id(
+            &request.merchant_id,
+            &state.config.secrets.tenant,
+            &master_keys,
+        )
+        .await
+        .change_context(error::ApiError::RetrieveDataFailed("merchant"))
+        .report_unwrap()?;
+
+    let merchant_dek = GcmAes256::new(merchant.enc_key.expose());
+
+    let tenant_id = state.config.secrets.tenant.clone();
+    let merchant_id = request.merchant_id.clone();
+    let customer_id = request.merchant_customer_id.clone();
+
+    if merchant_created {
+        state
+            .db
+            .append_ledger_record(
+                ledger::LedgerMetadata {
+                    operation: ledger::LedgerOperation::InsertMerchant,
+                    tenant_id: tenant_id.clone(),
+                    merchant_id: merchant_id.clone(),
+                    customer_id: None,
+                    data_type: None,
+                    reference_id: None,
+                    timestamp_millis: ledger::now_millis(),
+                },
+                &state.config.secrets.ledger_checkpoint_key,
+            )
+            .await
+            .change_context(error::ApiError::DatabaseInsertFailed("ledger"))
+            .report_unwrap()?;
+    }
+
+    let fingerprint = request
+        .fingerprint_source
+        .as_ref()
+        .map(|source| fingerprint::compute(source.peek(), &state.config.secrets.fingerprint_key))
+        .transpose()
+        .report_unwrap()?;
+
+    // If the fingerprint already matches data vaulted in this merchant (under any customer,
+    // including by an identical earlier request), `Reject` fails fast; `ReturnExisting` falls
+    // through to returning or inserting a row for *this* customer that references the
+    // already-vaulted content below, rather than handing back a
+    // reference that belongs to whichever customer the PAN was originally stored under.
+    let existing_by_fingerprint = match &fingerprint {
+        Some(fingerprint)
+            if !matches!(
+                request.duplicate_check.unwrap_or_default(),
+                fingerprint::DuplicateCheckMode::Off
+            ) =>
+        {
+            let existing = state
+                .db
+                .find_by_fingerprint(
+                    data_type,
+                    fingerprint,
+                    &state.config.secrets.tenant,
+                    &request.merchant_id,
+                    &merchant_dek,
+                )
+                .await
+                .change_context(error::ApiError::DatabaseRetrieveFailed("vault"))
+                .report_unwrap()?;
+
+            if existing.is_some()
+                && matches!(
+                    request.duplicate_check.unwrap_or_default(),
+                    fingerprint::DuplicateCheckMode::Reject
+                )
+            {
+                return Err(error::ApiError::DuplicateCardNumber);
+            }
+
+            existing
+        }
+        _ => None,
+    };
+
+    let output = match existing_by_fingerprint {
+        Some(existing_data) => {
+            // Reuse `existing_data`'s own `hash_id` and payload - never this request's, which may
+            // legitimately differ (e.g. in expiry or name) - so the hash table keeps mapping
+            // `hash_id` to the content it was actually computed over.
+            let stored_for_customer = state
+                .db
+                .find_by_hash_id_merchant_id_customer_id(
+                    data_type,
+                    &existing_data.hash_id,
+                    &state.config.secrets.tenant,
+                    &request.merchant_id,
+                    &request.merchant_customer_id,
+                    &merchant_dek,
+                )
+                .await
+                .change_context(error::ApiError::DatabaseRetrieveFailed("vault"))
+                .report_unwrap()?;
+
+            match stored_for_customer {
+                Some(data) => data,
+                None => {
+                    let vault_data = state
+                        .db
+                        .insert_or_get_from_vault(
+                            (
+                                &existing_data,
+                                data_type,
+                                state.config.secrets.tenant.as_str(),
+                                request.merchant_customer_id.as_str(),
+                            )
+                                .try_into()?,
+                            &merchant_dek,
+                        )
+                        .await
+                        .change_context(error::ApiError::DatabaseInsertFailed("vault"))
+                        .report_unwrap()?;
+
+                    append_insert_record(
+                        state,
+                        data_type,
+                        &tenant_id,
+                        &merchant_id,
+                        &customer_id,
+                        &vault_data.reference_id,
+                    )
+                    .await?;
+
+                    vault_data
+                }
+            }
+        }
+        None => {
+            let hash_data = serde_json::to_vec(&request.data)
+                .change_context(error::ApiError::EncodingError)
+                .and_then(|data| {
+                    (Sha512)
+                        .encode(data)
+                        .change_context(error::ApiError::EncodingError)
+                })
+                .report_unwrap()?;
+
+            let optional_hash_table = state
+                .db
+                .find_by_data_hash(&hash_data)
+                .await
+                .change_context(error::ApiError::DatabaseRetrieveFailed("hash_table"))
+                .report_unwrap()?;
+
+            let (hash_id, stored_data) = match optional_hash_table {
+                Some(hash_table) => {
+                    let stored_data = state
+                        .db
+                        .find_by_hash_id_merchant_id_customer_id(
+                            data_type,
+                            &hash_table.hash_id,
+                            &state.config.secrets.tenant,
+                            &request.merchant_id,
+                            &request.merchant_customer_id,
+                            &merchant_dek,
+                        )
+                        .await
+                        .change_context(error::ApiError::DatabaseRetrieveFailed("vault"))
+                        .report_unwrap()?;
+
+                    (hash_table.hash_id, stored_data)
+                }
+                None => {
+                    let hash_table = state
+                        .db
+                        .insert_hash(hash_data)
+                        .await
+                        .change_context(error::ApiError::DatabaseInsertFailed("hash_table"))
+                        .report_unwrap()?;
+
+                    state
+                        .db
+                        .append_ledger_record(
+                            ledger::LedgerMetadata {
+                                operation: ledger::LedgerOperation::InsertHash,
+                                tenant_id: tenant_id.clone(),
+                                merchant_id: merchant_id.clone(),
+                                customer_id: None,
+                                data_type: None,
+                                reference_id: None,
+                                timestamp_millis: ledger::now_millis(),
+                            },
+                            &state.config.secrets.ledger_checkpoint_key,
+                        )
+                        .await
+                        .change_context(error::ApiError::DatabaseInsertFailed("ledger"))
+                        .report_unwrap()?;
+
+                    (hash_table.hash_id, None)
+                }
+            };
+
+            match stored_data {
+                Some(data) => data,
+                None => {
+                    let vault_data = state
+                        .db
+                        .insert_or_get_from_vault(
+                            (
+                                request,
+                                data_type,
+                                state.config.secrets.tenant.as_str(),
+                                hash_id.as_str(),
+                                fingerprint.clone(),
+                            )
+                                .try_into()?,
+                            &merchant_dek,
+                        )
+                        .await
+                        .change_context(error::ApiError::DatabaseInsertFailed("vault"))
+                        .report_unwrap()?;
+
+                    append_insert_record(
+                        state,
+                        data_type,
+                        &tenant_id,
+                        &merchant_id,
+                        &customer_id,
+                        &vault_data.reference_id,
+                    )
+                    .await?;
+
+                    vault_data
+                }
+            }
+        }
+    };
+
+    let reference_id = output.reference_id.clone();
+    let mut response: types::StoreDataResponse = output.into();
+    response.fingerprint = fingerprint.as_deref().map(hex::encode);
+
+    Ok((reference_id, response))
+}
+
+async fn append_insert_record(
+    state: &AppState,
+    data_type: &str,
+    tenant_id: &str,
+    merchant_id: &str,
+    customer_id: &str,
+    reference_id: &str,
+) -> Result<(), error::ApiError> {
+    state
+        .db
+        .append_ledger_record(
+            ledger::LedgerMetadata {
+                operation: ledger::LedgerOperation::InsertVaultData,
+                tenant_id: tenant_id.to_owned(),
+                merchant_id: merchant_id.to_owned(),
+                customer_id: Some(customer_id.to_owned()),
+                data_type: Some(data_type.to_owned()),
+                reference_id: Some(reference_id.to_owned()),
+                timestamp_millis: ledger::now_millis(),
+            },
+            &state.config.secrets.ledger_checkpoint_key,
+        )
+        .await
+        .change_context(error::ApiError::DatabaseInsertFailed("ledger"))
+        .report_unwrap()?;
+
+    Ok(())
+}
+
+/// `/data/vault/delete` deleting vaulted data; the delete itself does not use the merchant's dek
+pub async fn delete_data(
+    extract::State(state): extract::State<AppState>,
+    Json(request): Json<types::DeleteDataRequest>,
+) -> Result<Json<types::DeleteDataResponse>, error::ApiError> {
+    let data_type = request.data_type.clone();
+    let response = delete(&state, &data_type, request).await?;
+
+    Ok(Json(response))
+}
+
+/// Delete vaulted data and emit the audit event and ledger record for the attempt. Shared by
+/// the generic `/data/vault` routes and the card-specific `/data` routes.
+pub(crate) async fn delete(
+    state: &AppState,
+    data_type: &str,
+    request: types::DeleteDataRequest,
+) -> Result<types::DeleteDataResponse, error::ApiError> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let merchant_id = request.merchant_id.clone();
+    let customer_id = request.merchant_customer_id.clone();
+    let reference = request.reference.peek().clone();
+    let started_at = std::time::Instant::now();
+
+    let result = delete_inner(state, data_type, request).await;
+
+    state
+        .db
+        .log_event(event::VaultEvent {
+            request_id,
+            event_type: event::EventType::DeleteData,
+            tenant_id: state.config.secrets.tenant.clone(),
+            merchant_id,
+            customer_id,
+            data_type: data_type.to_owned(),
+            reference: Some(reference),
+            outcome: if result.is_ok() {
+                event::EventOutcome::Success
+            } else {
+                event::EventOutcome::Failure
+            },
+            latency_ms: started_at.elapsed().as_millis(),
+        })
+        .await;
+
+    result
+}
+
+async fn delete_inner(
+    state: &AppState,
+    data_type: &str,
+    request: types::DeleteDataRequest,
+) -> Result<types::DeleteDataResponse, error::ApiError> {
+    let master_keys = keymanager::MasterKeyStore::from_config(&state.config.secrets);
+
+    let _merchant = state
+        .db
+        .find_by_merchant_id(
+            &request.merchant_id,
+            &state.config.secrets.tenant,
+            &master_keys,
+        )
+        .await
+        .change_context(error::ApiError::DatabaseRetrieveFailed("merchant"))
+        .report_unwrap()?;
+
+    let tenant_id = state.config.secrets.tenant.clone();
+    let merchant_id = request.merchant_id.clone();
+    let customer_id = request.merchant_customer_id.clone();
+    let reference_id = request.reference.peek().clone();
+
+    let _delete_status = state
+        .db
+        .delete_from_vault(
+            data_type,
+            request.reference.into(),
+            &state.config.secrets.tenant,
+            &request.merchant_id,
+            &request.merchant_customer_id,
+        )
+        .await
+        .change_context(error::ApiError::DatabaseDeleteFailed("vault"))
+        .report_unwrap()?;
+
+    state
+        .db
+        .append_ledger_record(
+            ledger::LedgerMetadata {
+                operation: ledger::LedgerOperation::DeleteVaultData,
+                tenant_id,
+                merchant_id,
+                customer_id: Some(customer_id),
+                data_type: Some(data_type.to_owned()),
+                reference_id: Some(reference_id),
+                timestamp_millis: ledger::now_millis(),
+            },
+            &state.config.secrets.ledger_checkpoint_key,
+        )
+        .await
+        .change_context(error::ApiError::DatabaseDeleteFailed("ledger"))
+        .report_unwrap()?;
+
+    Ok(types::DeleteDataResponse {
+        status: types::Status::Ok,
+    })
+}
+
+/// `/data/vault/retrieve` fetching vaulted data, decrypted with the merchant's dek
+pub async fn retrieve_data(
+    extract::State(state): extract::State<AppState>,
+    Json(request): Json<types::RetrieveDataRequest>,
+) -> Result<Json<types::RetrieveDataResponse>, error::ApiError> {
+    let data_type = request.data_type.clone();
+    let response = retrieve(&state, &data_type, request).await?;
+
+    Ok(Json(response))
+}
+
+/// Fetch vaulted data and emit the audit event for the attempt. Shared by the generic
+/// `/data/vault` routes and the card-specific `/data` routes.
+pub(crate) async fn retrieve(
+    state: &AppState,
+    data_type: &str,
+    request: types::RetrieveDataRequest,
+) -> Result<types::RetrieveDataResponse, error::ApiError> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let merchant_id = request.merchant_id.clone();
+    let customer_id = request.merchant_customer_id.clone();
+    let reference = request.reference.peek().clone();
+    let started_at = std::time::Instant::now();
+
+    let result = retrieve_inner(state, data_type, request).await;
+
+    state
+        .db
+        .log_event(event::VaultEvent {
+            request_id,
+            event_type: event::EventType::RetrieveData,
+            tenant_id: state.config.secrets.tenant.clone(),
+            merchant_id,
+            customer_id,
+            data_type: data_type.to_owned(),
+            reference: Some(reference),
+            outcome: if result.is_ok() {
+                event::EventOutcome::Success
+            } else {
+                event::EventOutcome::Failure
+            },
+            latency_ms: started_at.elapsed().as_millis(),
+        })
+        .await;
+
+    result
+}
+
+async fn retrieve_inner(
+    state: &AppState,
+    data_type: &str,
+    request: types::RetrieveDataRequest,
+) -> Result<types::RetrieveDataResponse, error::ApiError> {
+    let master_keys = keymanager::MasterKeyStore::from_config(&state.config.secrets);
+
+    let merchant = state
+        .db
+        .find_by_merchant_id(
+            &request.merchant_id,
+            &state.config.secrets.tenant,
+            &master_keys,
+        )
+        .await
+        .change_context(error::ApiError::DatabaseRetrieveFailed("merchant"))
+        .report_unwrap()?;
+
+    let merchant_dek = GcmAes256::new(merchant.enc_key.expose());
+
+    let data = state
+        .db
+        .find_by_reference_merchant_id_customer_id(
+            data_type,
+            request.reference.into(),
+            &state.config.secrets.tenant,
+            &request.merchant_id,
+            &request.merchant_customer_id,
+            &merchant_dek,
+        )
+        .await
+        .change_context(error::ApiError::DatabaseRetrieveFailed("vault"))
+        .report_unwrap()?;
+
+    Ok(data.try_into()?)
+}
@@ -2,6 +2,7 @@ use crate::{
     config::Database,
     crypto::Encryption,
     error::{self, ContainerError},
+    keymanager,
 };
 
 use std::sync::Arc;
@@ -17,6 +18,8 @@ use error_stack::ResultExt;
 use masking::{PeekInterface, Secret};
 
 pub mod db;
+pub mod event;
+pub mod ledger;
 pub mod schema;
 pub mod types;
 
@@ -26,13 +29,18 @@ pub trait State {}
 #[derive(Clone)]
 pub struct Storage {
     pg_pool: Arc<Pool<AsyncPgConnection>>,
+    event_sink: Arc<dyn event::EventSink>,
 }
 
 type DeadPoolConnType = Object<AsyncPgConnection>;
 
 impl Storage {
-    /// Create a new storage interface from configuration
-    pub async fn new(database: &Database) -> error_stack::Result<Self, error::StorageError> {
+    /// Create a new storage interface from configuration, emitting audit events through
+    /// `event_sink`
+    pub async fn new(
+        database: &Database,
+        event_sink: Arc<dyn event::EventSink>,
+    ) -> error_stack::Result<Self, error::StorageError> {
         let database_url = format!(
             "postgres://{}:{}@{}:{}/{}",
             database.username,
@@ -55,6 +63,7 @@ impl Storage {
             .change_context(error::StorageError::DBPoolError)?;
         Ok(Self {
             pg_pool: Arc::new(pool),
+            event_sink,
         })
     }
 
@@ -77,74 +86,111 @@ pub trait MerchantInterface {
     type Algorithm: Encryption<Vec<u8>, Vec<u8>>;
     type Error;
 
-    /// find merchant from merchant table with `merchant_id` and `tenant_id` with key as master key
+    /// find merchant from merchant table with `merchant_id` and `tenant_id`, decrypting its dek
+    /// with whichever master key version is recorded on the row
     async fn find_by_merchant_id(
         &self,
         merchant_id: &str,
         tenant_id: &str,
-        key: &Self::Algorithm,
+        keys: &keymanager::MasterKeyStore<Self::Algorithm>,
     ) -> Result<types::Merchant, ContainerError<Self::Error>>;
 
-    /// find merchant from merchant table with `merchant_id` and `tenant_id` with key as master key
-    /// and if not found create a new merchant
+    /// find merchant from merchant table with `merchant_id` and `tenant_id` and if not found
+    /// create a new merchant, encrypting its dek under `keys.current()`. The returned `bool` is
+    /// `true` when a new merchant was created, so callers can append the `InsertMerchant` ledger
+    /// record required by [`LedgerInterface`].
     async fn find_or_create_by_merchant_id(
         &self,
         merchant_id: &str,
         tenant_id: &str,
-        key: &Self::Algorithm,
-    ) -> Result<types::Merchant, ContainerError<Self::Error>>;
+        keys: &keymanager::MasterKeyStore<Self::Algorithm>,
+    ) -> Result<(types::Merchant, bool), ContainerError<Self::Error>>;
 
-    /// Insert a new merchant in the database by encrypting the dek with `master_key`
+    /// Insert a new merchant in the database by encrypting the dek with `keys.current()`
     async fn insert_merchant(
         &self,
         new: types::MerchantNew<'_>,
-        key: &Self::Algorithm,
+        keys: &keymanager::MasterKeyStore<Self::Algorithm>,
     ) -> Result<types::Merchant, ContainerError<Self::Error>>;
+
+    /// Re-encrypt every merchant's dek in `tenant_id` under `keys.current()`, streaming in
+    /// batches inside a transaction per batch. Only the dek is touched - card ciphertext is
+    /// never read or rewritten.
+    async fn rotate_master_key(
+        &self,
+        tenant_id: &str,
+        keys: &keymanager::MasterKeyStore<Self::Algorithm>,
+    ) -> Result<keymanager::RotationSummary, ContainerError<Self::Error>>;
 }
 
+/// Data type discriminator card endpoints vault under, now that the card endpoints are a thin
+/// specialization of [`DataVaultInterface`]
+pub const CARD_DATA_TYPE: &str = "card";
+
 ///
-/// LockerInterface:
+/// DataVaultInterface:
 ///
-/// Interface for interacting with the locker database table
+/// Generic, content-addressed vault for arbitrary opaque secrets (tokens, bank-account details,
+/// network tokens, card data), keyed by merchant + customer + a caller-supplied `data_type`
+/// discriminator (see [`CARD_DATA_TYPE`] for the card specialization). Supersedes the
+/// card-specific locker table interface: the same dedup-by-hash, per-merchant-encrypted storage
+/// engine now serves any payload shape.
 #[async_trait::async_trait]
-pub trait LockerInterface {
+pub trait DataVaultInterface {
     type Algorithm: Encryption<Vec<u8>, Vec<u8>>;
     type Error;
 
-    /// Fetch payment data from locker table by decrypting with `dek`
-    async fn find_by_locker_id_merchant_id_customer_id(
+    /// Fetch vaulted data by reference, decrypting with `dek`
+    async fn find_by_reference_merchant_id_customer_id(
         &self,
-        locker_id: Secret<String>,
+        data_type: &str,
+        reference: Secret<String>,
         tenant_id: &str,
         merchant_id: &str,
         customer_id: &str,
         key: &Self::Algorithm,
-    ) -> Result<types::Locker, ContainerError<Self::Error>>;
+    ) -> Result<types::VaultData, ContainerError<Self::Error>>;
 
-    /// Insert payment data from locker table by decrypting with `dek`
-    async fn insert_or_get_from_locker(
+    /// Insert vaulted data, encrypting with `dek`
+    async fn insert_or_get_from_vault(
         &self,
-        new: types::LockerNew<'_>,
+        new: types::VaultDataNew<'_>,
         key: &Self::Algorithm,
-    ) -> Result<types::Locker, ContainerError<Self::Error>>;
+    ) -> Result<types::VaultData, ContainerError<Self::Error>>;
 
-    /// Delete card from the locker, without access to the `dek`
-    async fn delete_from_locker(
+    /// Delete vaulted data, without access to the `dek`
+    async fn delete_from_vault(
         &self,
-        locker_id: Secret<String>,
+        data_type: &str,
+        reference: Secret<String>,
         tenant_id: &str,
         merchant_id: &str,
         customer_id: &str,
     ) -> Result<usize, ContainerError<Self::Error>>;
 
+    /// Look up data already vaulted for a given content hash, so identical payloads dedupe per
+    /// merchant/customer/data_type
     async fn find_by_hash_id_merchant_id_customer_id(
         &self,
+        data_type: &str,
         hash_id: &str,
         tenant_id: &str,
         merchant_id: &str,
         customer_id: &str,
         key: &Self::Algorithm,
-    ) -> Result<Option<types::Locker>, ContainerError<Self::Error>>;
+    ) -> Result<Option<types::VaultData>, ContainerError<Self::Error>>;
+
+    /// Look up vaulted data by its keyed fingerprint within a merchant, across customers, so the
+    /// same PAN can be detected regardless of which customer or payload it was originally stored
+    /// under. See [`crate::fingerprint`].
+    async fn find_by_fingerprint(
+        &self,
+        data_type: &str,
+        fingerprint: &[u8],
+        tenant_id: &str,
+        merchant_id: &str,
+        key: &Self::Algorithm,
+    ) -> Result<Option<types::VaultData>, ContainerError<Self::Error>>;
 }
 
 /// Trait defining behaviour of the application with the hash table, providing APIs to interact
@@ -162,3 +208,50 @@ pub trait HashInterface {
         data_hash: Vec<u8>,
     ) -> Result<types::HashTable, ContainerError<Self::Error>>;
 }
+
+///
+/// EventInterface:
+///
+/// Interface for emitting structured audit events for vault operations. Handlers fire one event
+/// per mutating or read operation; implementations must never fail or block the caller.
+#[async_trait::async_trait]
+pub trait EventInterface {
+    /// Emit a vault audit event through the configured sink
+    async fn log_event(&self, vault_event: event::VaultEvent);
+}
+
+#[async_trait::async_trait]
+impl EventInterface for Storage {
+    async fn log_event(&self, vault_event: event::VaultEvent) {
+        self.event_sink.emit(vault_event).await;
+    }
+}
+
+///
+/// LedgerInterface:
+///
+/// Interface for the tamper-evident, append-only operation ledger. The storage operations do not
+/// append to it themselves: callers of every state-changing operation (`insert_or_get_from_vault`,
+/// `delete_from_vault`, `insert_merchant`, `insert_hash`) append a record through
+/// [`LedgerInterface::append_ledger_record`], which chains it onto the current head and writes a
+/// checkpoint every [`ledger::CHECKPOINT_INTERVAL`] records.
+#[async_trait::async_trait]
+pub trait LedgerInterface {
+    type Error;
+
+    /// Append a state-changing operation to the hash chain, signing a new checkpoint with
+    /// `checkpoint_key` whenever one falls due
+    async fn append_ledger_record(
+        &self,
+        metadata: ledger::LedgerMetadata,
+        checkpoint_key: &Secret<Vec<u8>>,
+    ) -> Result<ledger::LedgerRecord, ContainerError<Self::Error>>;
+
+    /// Replay the ledger from its latest checkpoint - verifying the checkpoint's own signature
+    /// before trusting it as the replay's starting point - recomputing the hash chain, and
+    /// report whether it is intact along with the current head and height
+    async fn verify_ledger(
+        &self,
+        checkpoint_key: &Secret<Vec<u8>>,
+    ) -> Result<ledger::VerificationResult, ContainerError<Self::Error>>;
+}
@@ -0,0 +1,178 @@
+//! Structured audit events emitted for vault operations, and the sinks they can be shipped to.
+//!
+//! Events never carry card data or the DEK - only the metadata needed to build dashboards and
+//! alerting over vault traffic (event type, merchant/tenant/customer, outcome, latency).
+
+use std::sync::{atomic::AtomicU64, atomic::Ordering, Arc};
+use std::time::Duration;
+
+/// The vault operation a [`VaultEvent`] describes
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventType {
+    AddData,
+    DeleteData,
+    RetrieveData,
+}
+
+/// Outcome of the operation the event describes
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventOutcome {
+    Success,
+    Failure,
+}
+
+/// A single structured audit event for a mutating or read vault operation
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct VaultEvent {
+    pub request_id: String,
+    pub event_type: EventType,
+    pub tenant_id: String,
+    pub merchant_id: String,
+    pub customer_id: String,
+    /// Discriminator of the vaulted payload, e.g. [`super::CARD_DATA_TYPE`]
+    pub data_type: String,
+    pub reference: Option<String>,
+    pub outcome: EventOutcome,
+    pub latency_ms: u128,
+}
+
+/// A destination that vault events are shipped to.
+///
+/// Implementations must never block or fail the request path - errors should be logged (and, if
+/// relevant, counted) internally rather than surfaced to the caller.
+#[async_trait::async_trait]
+pub trait EventSink: Send + Sync {
+    async fn emit(&self, event: VaultEvent);
+}
+
+/// Sink that writes events as newline-delimited JSON to stdout, used when no external sink is
+/// configured
+#[derive(Debug, Default, Clone)]
+pub struct StdOutEventSink;
+
+#[async_trait::async_trait]
+impl EventSink for StdOutEventSink {
+    async fn emit(&self, event: VaultEvent) {
+        match serde_json::to_string(&event) {
+            Ok(line) => println!("{line}"),
+            Err(error) => tracing::error!(?error, "failed to serialize vault event"),
+        }
+    }
+}
+
+/// Configuration for the batching ClickHouse sink
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct ClickHouseSinkConfig {
+    pub url: String,
+    pub database: String,
+    pub table: String,
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    #[serde(default = "default_flush_interval_ms")]
+    pub flush_interval_ms: u64,
+    #[serde(default = "default_channel_capacity")]
+    pub channel_capacity: usize,
+}
+
+fn default_batch_size() -> usize {
+    500
+}
+
+fn default_flush_interval_ms() -> u64 {
+    1000
+}
+
+fn default_channel_capacity() -> usize {
+    10_000
+}
+
+/// Sink that buffers events in memory and ships them as newline-delimited JSON rows to
+/// ClickHouse over HTTP, flushed whenever the buffer hits `batch_size` or `flush_interval_ms`
+/// elapses, whichever comes first.
+///
+/// The hot path never blocks on this sink: events are pushed onto a bounded channel, and rows
+/// are dropped (with `dropped_count` incremented) if the background flusher can't keep up.
+#[derive(Clone)]
+pub struct ClickHouseEventSink {
+    sender: tokio::sync::mpsc::Sender<VaultEvent>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl ClickHouseEventSink {
+    pub fn new(config: ClickHouseSinkConfig, client: reqwest::Client) -> Self {
+        let (sender, receiver) = tokio::sync::mpsc::channel(config.channel_capacity);
+        let dropped = Arc::new(AtomicU64::new(0));
+
+        tokio::spawn(Self::run(config, client, receiver));
+
+        Self { sender, dropped }
+    }
+
+    /// Number of events dropped so far because the sink could not keep up with traffic
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    async fn run(
+        config: ClickHouseSinkConfig,
+        client: reqwest::Client,
+        mut receiver: tokio::sync::mpsc::Receiver<VaultEvent>,
+    ) {
+        let mut buffer = Vec::with_capacity(config.batch_size);
+        let mut ticker = tokio::time::interval(Duration::from_millis(config.flush_interval_ms));
+
+        loop {
+            tokio::select! {
+                event = receiver.recv() => {
+                    match event {
+                        Some(event) => {
+                            buffer.push(event);
+                            if buffer.len() >= config.batch_size {
+                                Self::flush(&config, &client, &mut buffer).await;
+                            }
+                        }
+                        None => {
+                            Self::flush(&config, &client, &mut buffer).await;
+                            return;
+                        }
+                    }
+                }
+                _ = ticker.tick() => {
+                    Self::flush(&config, &client, &mut buffer).await;
+                }
+            }
+        }
+    }
+
+    async fn flush(config: &ClickHouseSinkConfig, client: &reqwest::Client, buffer: &mut Vec<VaultEvent>) {
+        if buffer.is_empty() {
+            return;
+        }
+
+        let body = buffer
+            .drain(..)
+            .filter_map(|event| serde_json::to_string(&event).ok())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let url = format!(
+            "{}/?query=INSERT+INTO+{}.{}+FORMAT+JSONEachRow",
+            config.url, config.database, config.table
+        );
+
+        if let Err(error) = client.post(url).body(body).send().await {
+            tracing::error!(?error, "failed to ship vault events to clickhouse");
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventSink for ClickHouseEventSink {
+    async fn emit(&self, event: VaultEvent) {
+        if self.sender.try_send(event).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
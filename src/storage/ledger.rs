@@ -0,0 +1,165 @@
+//! Tamper-evident, append-only operation ledger.
+//!
+//! Every state-changing storage operation is recorded as a [`LedgerRecord`] forming a hash
+//! chain: each record's `record_hash` is `Sha512(prev_hash || canonical(metadata))`. Every
+//! [`CHECKPOINT_INTERVAL`] records a [`Checkpoint`] is written recording the chain head, signed
+//! with a dedicated checkpoint key so the checkpoint itself can't be forged, so [`verify_chain`]
+//! only has to replay records since the latest checkpoint to detect tampering or deletion of
+//! rows in the backing store.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use error_stack::ResultExt;
+use masking::Secret;
+
+use crate::{
+    crypto::{hmac::HmacSha512, sha::Sha512, Encode},
+    error,
+};
+
+/// Number of records between automatic checkpoints
+pub const CHECKPOINT_INTERVAL: i64 = 64;
+
+/// The kind of state-changing operation a [`LedgerMetadata`] records
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerOperation {
+    InsertVaultData,
+    DeleteVaultData,
+    InsertMerchant,
+    InsertHash,
+}
+
+/// Metadata describing a state-changing operation. Never contains plaintext card data - only
+/// enough to audit what happened and to whom.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LedgerMetadata {
+    pub operation: LedgerOperation,
+    pub tenant_id: String,
+    pub merchant_id: String,
+    pub customer_id: Option<String>,
+    /// Discriminator of the vaulted payload the operation concerns, e.g. [`super::CARD_DATA_TYPE`]
+    pub data_type: Option<String>,
+    pub reference_id: Option<String>,
+    pub timestamp_millis: u128,
+}
+
+/// A single entry in the hash chain
+#[derive(Debug, Clone)]
+pub struct LedgerRecord {
+    pub seq: i64,
+    pub prev_hash: Vec<u8>,
+    pub metadata: LedgerMetadata,
+    pub record_hash: Vec<u8>,
+}
+
+/// A periodic snapshot of the chain head, used as the starting point for verification so a
+/// replay never has to walk the whole table. `signature` is an HMAC-SHA512 over `seq ||
+/// head_hash` keyed with a dedicated checkpoint-signing key, so a tampered or forged checkpoint
+/// row is itself detected rather than implicitly trusted as the replay's starting point.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    pub seq: i64,
+    pub head_hash: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+/// Sign `seq || head_hash` with the checkpoint-signing key, used both when a new checkpoint is
+/// written and when an existing one is verified before replay
+pub fn compute_checkpoint_signature(
+    seq: i64,
+    head_hash: &[u8],
+    checkpoint_key: &Secret<Vec<u8>>,
+) -> error_stack::Result<Vec<u8>, error::StorageError> {
+    let mut data = seq.to_be_bytes().to_vec();
+    data.extend_from_slice(head_hash);
+
+    HmacSha512::new(checkpoint_key.clone())
+        .encode(data)
+        .change_context(error::StorageError::EncodingError)
+}
+
+/// Result of replaying the ledger from its latest checkpoint
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct VerificationResult {
+    pub verified: bool,
+    pub head_hash: Vec<u8>,
+    pub height: i64,
+}
+
+/// Milliseconds since the Unix epoch, used as the `timestamp_millis` of a [`LedgerMetadata`]
+pub fn now_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+/// Canonically serialize `metadata` and fold it onto `prev_hash` to get the next record hash
+pub fn compute_record_hash(
+    prev_hash: &[u8],
+    metadata: &LedgerMetadata,
+) -> error_stack::Result<Vec<u8>, error::StorageError> {
+    let mut data = prev_hash.to_vec();
+    data.extend(
+        serde_json::to_vec(metadata).change_context(error::StorageError::EncodingError)?,
+    );
+
+    (Sha512)
+        .encode(data)
+        .change_context(error::StorageError::EncodingError)
+}
+
+/// Verify `checkpoint`'s signature, then replay `records` - which must be ordered by `seq` and
+/// start immediately after `checkpoint` - recomputing the chain hash-by-hash. Returns as soon as
+/// a signature, `prev_hash`, or `record_hash` mismatch is found. On a `prev_hash` or
+/// `record_hash` mismatch, `height` is the `seq` of the last record that verified, so the
+/// tampered or missing row is the one right after it; on a bad checkpoint signature, `height` is
+/// the checkpoint's own `seq`.
+pub fn verify_chain(
+    checkpoint: &Checkpoint,
+    records: &[LedgerRecord],
+    checkpoint_key: &Secret<Vec<u8>>,
+) -> error_stack::Result<VerificationResult, error::StorageError> {
+    let expected_signature =
+        compute_checkpoint_signature(checkpoint.seq, &checkpoint.head_hash, checkpoint_key)?;
+
+    if expected_signature != checkpoint.signature {
+        return Ok(VerificationResult {
+            verified: false,
+            head_hash: checkpoint.head_hash.clone(),
+            height: checkpoint.seq,
+        });
+    }
+
+    let mut head = checkpoint.head_hash.clone();
+    let mut height = checkpoint.seq;
+
+    for record in records {
+        if record.prev_hash != head {
+            return Ok(VerificationResult {
+                verified: false,
+                head_hash: head,
+                height,
+            });
+        }
+
+        let expected_hash = compute_record_hash(&head, &record.metadata)?;
+        if expected_hash != record.record_hash {
+            return Ok(VerificationResult {
+                verified: false,
+                head_hash: head,
+                height,
+            });
+        }
+
+        head = record.record_hash.clone();
+        height = record.seq;
+    }
+
+    Ok(VerificationResult {
+        verified: true,
+        head_hash: head,
+        height,
+    })
+}